@@ -1,7 +1,15 @@
-use sfml::{graphics::*, system::*, window::*};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use sfml::{audio::*, graphics::*, system::*, window::*};
 use std::collections::HashMap;
 use std::f32::consts::PI;
+use std::fs::File;
 use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
 
 /// draw tile on screen
 fn draw_tile(
@@ -28,6 +36,24 @@ fn draw_tile(
     window.draw(&sp);
 }
 
+/// draw a fading, speed-tinted trail through a list of (position, value) points
+fn draw_trail(points: &[(Vector2f, f32)], gradient: &Gradient, window: &mut RenderWindow) {
+    if points.len() < 2 {
+        return;
+    }
+
+    let mut va = VertexArray::new(PrimitiveType::LineStrip, points.len());
+
+    for (i, (pos, value)) in points.iter().enumerate() {
+        let mut color = gradient.sample(*value);
+        color.a = ((i as f32 / (points.len() - 1) as f32) * 255.0) as u8;
+
+        va[i] = Vertex::new(*pos, color, Vector2f::new(0.0, 0.0));
+    }
+
+    window.draw(&va);
+}
+
 /// deg to rad
 fn d2r(deg: f32) -> f32 {
     deg / 360.0 * (PI * 2.0)
@@ -78,6 +104,18 @@ fn v2_angle_to_point(a: Vector2f, b: Vector2f) -> f32 {
     dis.y.atan2(dis.x)
 }
 
+/// wrap an angle in radians to [-PI, PI]
+fn normalize_angle(rad: f32) -> f32 {
+    let mut a = rad % (PI * 2.0);
+    if a > PI {
+        a -= PI * 2.0;
+    }
+    if a < -PI {
+        a += PI * 2.0;
+    }
+    a
+}
+
 fn v2_set_rotation(rad: f32) -> Vector2f {
     Vector2f::new(rad.cos(), rad.sin())
 }
@@ -98,26 +136,119 @@ fn v2_rotated(v: Vector2f, by: f32) -> Vector2f {
     Vector2f::new(new_x, new_y)
 }
 
+// TRACK BOUNDS ---
+
+/// a boundary edge segment tagged with its outward-facing normal
+#[derive(Clone, Copy)]
+struct BoundSegment {
+    a: Vector2f,
+    b: Vector2f,
+    normal: Vector2f,
+}
+
+impl BoundSegment {
+    fn new(a: Vector2f, b: Vector2f, normal: Vector2f) -> Self {
+        Self { a, b, normal }
+    }
+
+    /// this segment pushed outward along its normal, eg. to account for wheel radius
+    fn expanded(&self, amount: f32) -> Self {
+        let offset = self.normal * amount;
+        Self::new(self.a + offset, self.b + offset, self.normal)
+    }
+
+    /// closest point on the segment to `p`, and the signed distance from it along the
+    /// normal (positive means `p` has crossed to the outside of the segment)
+    fn test(&self, p: Vector2f) -> (Vector2f, f32) {
+        let edge = self.b - self.a;
+        let len_sq = v2_length_sq(edge);
+
+        let t = if len_sq > 0.0 {
+            v2_dot(p - self.a, edge) / len_sq
+        } else {
+            0.0
+        }
+        .clamp(0.0, 1.0);
+
+        let closest = self.a + edge * t;
+        let side = v2_dot(p - closest, self.normal);
+
+        (closest, side)
+    }
+}
+
+/// boundary lines for a track, derived from `TileLayer`. `red` is the true edge of the
+/// track surface, `green` is the same edges pushed outward by the wheel radius, so a
+/// wheel is considered off-track once it crosses to the outside of the green line
+struct TrackBounds {
+    #[allow(dead_code)]
+    red: Vec<BoundSegment>,
+    green: Vec<BoundSegment>,
+}
+
+impl TrackBounds {
+    fn new(tl: &TileLayer, tile_w: f32, tile_h: f32, wheel_radius: f32) -> Self {
+        let red = tl.boundary_edges(tile_w, tile_h);
+        let green = red.iter().map(|s| s.expanded(wheel_radius)).collect();
+
+        Self { red, green }
+    }
+
+    /// the normal and signed side of the nearest green edge to `point`
+    fn nearest(&self, point: Vector2f) -> Option<(Vector2f, f32)> {
+        let mut nearest: Option<(f32, Vector2f, f32)> = None;
+
+        for seg in &self.green {
+            let (closest, side) = seg.test(point);
+            let dist_sq = v2_length_sq(point - closest);
+
+            if nearest.map_or(true, |(best, ..)| dist_sq < best) {
+                nearest = Some((dist_sq, seg.normal, side));
+            }
+        }
+
+        nearest.map(|(_, normal, side)| (normal, side))
+    }
+
+    /// true once `point` has crossed to the outside of the nearest green edge
+    fn is_off_track(&self, point: Vector2f) -> bool {
+        self.nearest(point).map_or(false, |(_, side)| side > 0.0)
+    }
+
+    /// push `point` back along the segment normal if it has gone outside the green line
+    fn push_back(&self, point: Vector2f) -> Vector2f {
+        match self.nearest(point) {
+            Some((normal, side)) if side > 0.0 => point - normal * side,
+            _ => point,
+        }
+    }
+}
+
 // TILE LAYERS ---
 
-struct TileLayer {
+/// a serializable track definition: raw tile indices per layer (`[grass, track]`), each
+/// `rows * cols` long, plus the grid dimensions they're laid out on. decouples track
+/// content from any particular hardcoded size so maps can be authored and shared as files
+#[derive(Serialize, Deserialize)]
+struct TrackMap {
     rows: i32,
     cols: i32,
     tile_sheet_cols: i32,
-    tile_sheets: [[i32; 70]; 2],
-    tile_sheets_info: [[(bool, i32, i32, i32, i32); 70]; 2],
+    layers: Vec<Vec<i32>>,
 }
 
-impl TileLayer {
-    fn new() -> Self {
-        let track = [
+impl TrackMap {
+    /// the original built-in 10x7 circuit, kept as the default so a fresh run still has
+    /// something to drive on without a saved map on disk
+    fn default_track() -> Self {
+        let track = vec![
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 149, 185, 185, 185, 185, 185, 185, 131, 0, 0, 202, 94,
             183, 183, 183, 183, 76, 166, 0, 0, 307, 271, 0, 0, 0, 0, 202, 166, 0, 0, 202, 93, 185,
             185, 185, 185, 75, 166, 0, 0, 148, 183, 183, 183, 183, 183, 183, 130, 0, 0, 0, 0, 0, 0,
             0, 0, 0, 0, 0,
         ];
 
-        let grass = [
+        let grass = vec![
             102, 281, 281, 281, 281, 281, 281, 281, 281, 84, 30, 70, 70, 70, 70, 70, 70, 70, 70,
             66, 30, 70, 70, 70, 70, 70, 70, 70, 70, 66, 30, 70, 70, 70, 70, 70, 70, 70, 70, 66, 30,
             70, 70, 70, 70, 70, 70, 70, 70, 66, 30, 70, 70, 70, 70, 70, 70, 70, 70, 66, 12, 209,
@@ -128,17 +259,121 @@ impl TileLayer {
             rows: 7,
             cols: 10,
             tile_sheet_cols: 18,
-            tile_sheets: [grass, track],
-            tile_sheets_info: [[(false, 0, 0, 0, 0); 70]; 2],
+            layers: vec![grass, track],
+        }
+    }
+}
+
+struct TileLayer {
+    rows: i32,
+    cols: i32,
+    tile_sheet_cols: i32,
+    tile_sheets: Vec<Vec<i32>>,
+    tile_sheets_info: Vec<Vec<(bool, i32, i32, i32, i32)>>,
+}
+
+impl TileLayer {
+    fn new() -> Self {
+        Self::from_map(TrackMap::default_track())
+    }
+
+    fn from_map(map: TrackMap) -> Self {
+        let tile_sheets_info = map
+            .layers
+            .iter()
+            .map(|layer| vec![(false, 0, 0, 0, 0); layer.len()])
+            .collect();
+
+        Self {
+            rows: map.rows,
+            cols: map.cols,
+            tile_sheet_cols: map.tile_sheet_cols,
+            tile_sheets: map.layers,
+            tile_sheets_info,
         }
     }
 
-    fn get_info(&self) -> &[[(bool, i32, i32, i32, i32); 70]; 2] {
+    fn to_map(&self) -> TrackMap {
+        TrackMap {
+            rows: self.rows,
+            cols: self.cols,
+            tile_sheet_cols: self.tile_sheet_cols,
+            layers: self.tile_sheets.clone(),
+        }
+    }
+
+    /// load a `TrackMap` saved with `save` and build the tile layers from it
+    fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let map: TrackMap = bincode::deserialize_from(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        Ok(Self::from_map(map))
+    }
+
+    /// save the current track as a `TrackMap` binary file
+    fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        bincode::serialize_into(writer, &self.to_map())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn get_info(&self) -> &Vec<Vec<(bool, i32, i32, i32, i32)>> {
         &self.tile_sheets_info
     }
 
+    /// true if (x, y) is a track tile, false if it's off the grid or not track
+    fn is_track(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.cols || y >= self.rows {
+            return false;
+        }
+
+        let coord = x + self.cols * y;
+
+        self.tile_sheets[1]
+            .get(coord as usize)
+            .map_or(false, |num| *num > 0)
+    }
+
+    /// boundary edges between track tiles and their non-track neighbours (grass or the
+    /// edge of the grid), each tagged with the normal pointing away from the track. this
+    /// traces both the inner and outer edge of the track loop, since a track tile bordered
+    /// on opposite sides contributes a segment on each side
+    fn boundary_edges(&self, tile_w: f32, tile_h: f32) -> Vec<BoundSegment> {
+        let mut edges = Vec::new();
+
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                if !self.is_track(x, y) {
+                    continue;
+                }
+
+                let top_left = Vector2f::new(x as f32 * tile_w, y as f32 * tile_h);
+                let top_right = Vector2f::new((x + 1) as f32 * tile_w, y as f32 * tile_h);
+                let bottom_left = Vector2f::new(x as f32 * tile_w, (y + 1) as f32 * tile_h);
+                let bottom_right = Vector2f::new((x + 1) as f32 * tile_w, (y + 1) as f32 * tile_h);
+
+                if !self.is_track(x - 1, y) {
+                    edges.push(BoundSegment::new(top_left, bottom_left, Vector2f::new(-1.0, 0.0)));
+                }
+                if !self.is_track(x + 1, y) {
+                    edges.push(BoundSegment::new(top_right, bottom_right, Vector2f::new(1.0, 0.0)));
+                }
+                if !self.is_track(x, y - 1) {
+                    edges.push(BoundSegment::new(top_left, top_right, Vector2f::new(0.0, -1.0)));
+                }
+                if !self.is_track(x, y + 1) {
+                    edges.push(BoundSegment::new(bottom_left, bottom_right, Vector2f::new(0.0, 1.0)));
+                }
+            }
+        }
+
+        edges
+    }
+
     fn set_up(&mut self) {
-        for i in 0..2 {
+        for i in 0..self.tile_sheets.len() {
             //
             for x in 0..self.cols {
                 for y in 0..self.rows {
@@ -161,6 +396,172 @@ impl TileLayer {
     }
 }
 
+// PROCEDURAL TRACK ---
+
+const PROC_GRASS_TILE: i32 = 70;
+const PROC_STRAIGHT_EW: i32 = 185;
+const PROC_STRAIGHT_NS: i32 = 202;
+// named for the pair of sides each corner piece actually connects, per the hand-authored
+// default track (eg. PROC_CORNER_ES joins the east and south edges of its cell)
+const PROC_CORNER_ES: i32 = 149;
+const PROC_CORNER_WS: i32 = 131;
+const PROC_CORNER_EN: i32 = 148;
+const PROC_CORNER_WN: i32 = 130;
+
+const PROC_NORTH: (i32, i32) = (0, -1);
+const PROC_SOUTH: (i32, i32) = (0, 1);
+const PROC_EAST: (i32, i32) = (1, 0);
+const PROC_WEST: (i32, i32) = (-1, 0);
+
+impl TileLayer {
+    /// build a random closed-loop track from tile pieces, as a self-avoiding walk on the
+    /// grid that's biased to return to its start. reproducible for a given `seed`; retries
+    /// up to 64 walks (each limited to a step budget) and falls back to the hand-authored
+    /// circuit if none of the attempts manage to close
+    fn generate(seed: u64, rows: i32, cols: i32) -> Self {
+        const ATTEMPTS: u32 = 64;
+
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        for _ in 0..ATTEMPTS {
+            if let Some(loop_cells) = Self::walk_loop(&mut rng, rows, cols) {
+                let mut track = vec![0; (rows * cols) as usize];
+
+                for i in 0..loop_cells.len() {
+                    let prev = loop_cells[(i + loop_cells.len() - 1) % loop_cells.len()];
+                    let cur = loop_cells[i];
+                    let next = loop_cells[(i + 1) % loop_cells.len()];
+
+                    let entered_from = (cur.0 - prev.0, cur.1 - prev.1);
+                    let leaving_to = (next.0 - cur.0, next.1 - cur.1);
+
+                    let tile = Self::tile_for_directions(entered_from, leaving_to);
+                    track[(cur.0 + cols * cur.1) as usize] = tile;
+                }
+
+                let grass = vec![PROC_GRASS_TILE; (rows * cols) as usize];
+
+                return Self::from_map(TrackMap {
+                    rows,
+                    cols,
+                    tile_sheet_cols: 18,
+                    layers: vec![grass, track],
+                });
+            }
+        }
+
+        Self::new()
+    }
+
+    /// a self-avoiding orthogonal walk that's allowed to close back onto its own start
+    /// once it's long enough to form a real loop, or gives up (`None`) if it runs out of
+    /// room before it can close
+    fn walk_loop(rng: &mut StdRng, rows: i32, cols: i32) -> Option<Vec<(i32, i32)>> {
+        const STEP_BUDGET: usize = 200;
+        const MIN_LOOP_LEN: usize = 6;
+
+        let start = (cols / 2, rows / 2);
+        let mut path = vec![start];
+        let mut visited = std::collections::HashSet::new();
+        visited.insert(start);
+
+        let dirs = [PROC_NORTH, PROC_SOUTH, PROC_EAST, PROC_WEST];
+
+        for _ in 0..STEP_BUDGET {
+            let current = *path.last().unwrap();
+            let can_close = path.len() >= MIN_LOOP_LEN;
+
+            let mut candidates: Vec<(i32, i32)> = dirs
+                .iter()
+                .map(|d| (current.0 + d.0, current.1 + d.1))
+                .filter(|p| p.0 >= 0 && p.1 >= 0 && p.0 < cols && p.1 < rows)
+                .filter(|p| (*p == start && can_close) || !visited.contains(p))
+                .collect();
+
+            if candidates.is_empty() {
+                return None;
+            }
+
+            candidates.shuffle(rng);
+            // once the walk is long enough to close, often lean it back towards its start
+            if can_close && rng.gen_bool(0.5) {
+                candidates.sort_by_key(|p| (p.0 - start.0).abs() + (p.1 - start.1).abs());
+            }
+            let next = candidates[0];
+
+            if next == start && can_close {
+                return Some(path);
+            }
+
+            path.push(next);
+            visited.insert(next);
+        }
+
+        None
+    }
+
+    /// the straight/corner tile for a cell, given the step that entered it (`cur - prev`)
+    /// and the step that leaves it (`next - cur`)
+    fn tile_for_directions(entered_from: (i32, i32), leaving_to: (i32, i32)) -> i32 {
+        let entry_side = (-entered_from.0, -entered_from.1);
+        let exit_side = leaving_to;
+
+        let sides = (entry_side, exit_side);
+
+        match sides {
+            (PROC_NORTH, PROC_SOUTH) | (PROC_SOUTH, PROC_NORTH) => PROC_STRAIGHT_NS,
+            (PROC_EAST, PROC_WEST) | (PROC_WEST, PROC_EAST) => PROC_STRAIGHT_EW,
+            (PROC_NORTH, PROC_EAST) | (PROC_EAST, PROC_NORTH) => PROC_CORNER_EN,
+            (PROC_NORTH, PROC_WEST) | (PROC_WEST, PROC_NORTH) => PROC_CORNER_WN,
+            (PROC_SOUTH, PROC_EAST) | (PROC_EAST, PROC_SOUTH) => PROC_CORNER_ES,
+            (PROC_SOUTH, PROC_WEST) | (PROC_WEST, PROC_SOUTH) => PROC_CORNER_WS,
+            _ => PROC_STRAIGHT_EW,
+        }
+    }
+
+    /// an ordered list of waypoints through track tile centres, for AI pathing. starting
+    /// from the first track tile in row order, repeatedly step to the first unvisited track
+    /// neighbour (north, south, east, west) until there are no new cells to walk to. on a
+    /// one-tile-wide loop this visits every tile in order; on a wider track the walk can
+    /// stop before every tile is visited, and its last waypoint need not neighbour its first
+    fn centerline(&self, tile_w: f32, tile_h: f32) -> Vec<Vector2f> {
+        let mut cells = Vec::new();
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                if self.is_track(x, y) {
+                    cells.push((x, y));
+                }
+            }
+        }
+
+        let start = match cells.first() {
+            Some(&cell) => cell,
+            None => return Vec::new(),
+        };
+
+        let dirs = [PROC_NORTH, PROC_SOUTH, PROC_EAST, PROC_WEST];
+        let mut visited = std::collections::HashSet::new();
+        visited.insert(start);
+        let mut ordered = vec![start];
+        let mut current = start;
+
+        while let Some(next) = dirs
+            .iter()
+            .map(|d| (current.0 + d.0, current.1 + d.1))
+            .find(|p| self.is_track(p.0, p.1) && !visited.contains(p))
+        {
+            ordered.push(next);
+            visited.insert(next);
+            current = next;
+        }
+
+        ordered
+            .into_iter()
+            .map(|(x, y)| Vector2f::new((x as f32 + 0.5) * tile_w, (y as f32 + 0.5) * tile_h))
+            .collect()
+    }
+}
+
 // TEXTURE MANAGER ---
 pub struct TextureManager<ID: Hash + Eq> {
     // sfbox -> pointer to an SFML-allocated object
@@ -195,6 +596,251 @@ where
     }
 }
 
+// SOUND MANAGER ---
+pub struct SoundManager<ID: Hash + Eq> {
+    // sfbox -> pointer to an SFML-allocated object
+    buffer_map: HashMap<ID, SfBox<SoundBuffer>>,
+}
+
+impl<ID> SoundManager<ID>
+where
+    ID: Hash + Eq,
+{
+    /// add sound buffer
+    pub fn load(&mut self, id: ID, file_path: &str) {
+        let new_buffer = SoundBuffer::from_file(file_path).unwrap();
+        self.buffer_map.insert(id, new_buffer);
+    }
+
+    /// get sound buffer by its id
+    pub fn get(&self, key: ID) -> &SoundBuffer {
+        &self.buffer_map.get(&key).unwrap()
+    }
+}
+
+// set default values
+impl<ID> Default for SoundManager<ID>
+where
+    ID: Hash + Eq,
+{
+    fn default() -> Self {
+        Self {
+            buffer_map: HashMap::default(),
+        }
+    }
+}
+
+
+// GRADIENT ---
+
+/// a value-to-color gradient defined by sorted `(value, color)` stops, general enough to
+/// reuse anywhere a value needs to map to a color (speed trails, the gauge redline zone,
+/// off-track tinting, ...)
+struct Gradient {
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    fn new(stops: Vec<(f32, Color)>) -> Self {
+        Self { stops }
+    }
+
+    /// the color for `v`, clamped to the first/last stop outside the gradient's range and
+    /// linearly interpolated between the bracketing stops otherwise
+    fn sample(&self, v: f32) -> Color {
+        match self.stops.iter().position(|(value, _)| *value > v) {
+            None => self.stops.last().unwrap().1,
+            Some(0) => self.stops[0].1,
+            Some(i) => {
+                let (left_value, left) = self.stops[i - 1];
+                let (right_value, right) = self.stops[i];
+                let a = (v - left_value) / (right_value - left_value);
+
+                Color::rgba(
+                    lerp_channel(left.r, right.r, a),
+                    lerp_channel(left.g, right.g, a),
+                    lerp_channel(left.b, right.b, a),
+                    lerp_channel(left.a, right.a, a),
+                )
+            }
+        }
+    }
+}
+
+fn lerp_channel(left: u8, right: u8, a: f32) -> u8 {
+    (left as f32 * (1.0 - a) + right as f32 * a) as u8
+}
+
+// GAUGE HUD ---
+
+/// analog needle gauge, eg. speedometer/tachometer
+struct Gauge {
+    center: Vector2f,
+    radius: f32,
+    gauge_zero: f32, // needle angle in degrees at value 0
+    gauge_max: f32,  // value at full deflection
+    redline: f32,    // value at which the overspeed zone starts
+}
+
+impl Gauge {
+    fn new(center: Vector2f, radius: f32, gauge_zero: f32, gauge_max: f32, redline: f32) -> Self {
+        Self {
+            center,
+            radius,
+            gauge_zero,
+            gauge_max,
+            redline,
+        }
+    }
+
+    /// needle angle in radians for a value, sweeping from +gauge_zero to -gauge_zero as
+    /// value goes from 0 to gauge_max
+    fn angle_for(&self, value: f32) -> f32 {
+        let deg = self.gauge_zero - 2.0 * self.gauge_zero * (value / self.gauge_max);
+        d2r(deg)
+    }
+
+    fn needle_point(&self, angle: f32, length: f32) -> Vector2f {
+        self.center + v2_rotated(Vector2f::new(length, 0.0), angle)
+    }
+
+    fn draw(&self, value: f32, window: &mut RenderWindow) {
+        let redline_angle = self.angle_for(self.redline.min(self.gauge_max));
+        let redline_tip = self.needle_point(redline_angle, self.radius);
+
+        let tex_zero = Vector2f::new(0.0, 0.0);
+
+        let mut redline_marker = VertexArray::new(PrimitiveType::Lines, 2);
+        redline_marker[0] = Vertex::new(self.center, Color::rgb(200, 40, 40), tex_zero);
+        redline_marker[1] = Vertex::new(redline_tip, Color::rgb(200, 40, 40), tex_zero);
+        window.draw(&redline_marker);
+
+        let needle_angle = self.angle_for(value.clamp(0.0, self.gauge_max));
+        let needle_tip = self.needle_point(needle_angle, self.radius);
+
+        let mut needle = VertexArray::new(PrimitiveType::Lines, 2);
+        needle[0] = Vertex::new(self.center, Color::WHITE, tex_zero);
+        needle[1] = Vertex::new(needle_tip, Color::WHITE, tex_zero);
+        window.draw(&needle);
+    }
+}
+
+// MINIMAP ---
+
+/// a scaled-down view of the track anchored to a screen-space rect, so world positions
+/// (eg. the car, future opponents) can be plotted as small markers on it
+struct Minimap {
+    world_size: Vector2f,
+    target: FloatRect,
+}
+
+impl Minimap {
+    fn new(world_size: Vector2f, target: FloatRect) -> Self {
+        Self { world_size, target }
+    }
+
+    fn scale(&self) -> Vector2f {
+        Vector2f::new(
+            self.target.width / self.world_size.x,
+            self.target.height / self.world_size.y,
+        )
+    }
+
+    fn to_minimap(&self, world_pos: Vector2f) -> Vector2f {
+        let scale = self.scale();
+
+        Vector2f::new(
+            self.target.left + world_pos.x * scale.x,
+            self.target.top + world_pos.y * scale.y,
+        )
+    }
+
+    fn draw_track(&self, tl: &TileLayer, tile_w: f32, tile_h: f32, window: &mut RenderWindow) {
+        let scale = self.scale();
+
+        let mut background = RectangleShape::with_size(Vector2f::new(self.target.width, self.target.height));
+        background.set_position(Vector2f::new(self.target.left, self.target.top));
+        background.set_fill_color(Color::rgba(10, 10, 10, 180));
+        window.draw(&background);
+
+        let mut tile = RectangleShape::with_size(Vector2f::new(tile_w * scale.x, tile_h * scale.y));
+        tile.set_fill_color(Color::rgb(200, 200, 200));
+
+        // tile_sheets is laid out as [grass, track] - only the track layer should stand
+        // out against the dark background, otherwise the grass layer (also non-empty
+        // almost everywhere) paints the whole minimap one solid color
+        if let Some(track_layer) = tl.get_info().get(1) {
+            for info in track_layer.iter() {
+                if !info.0 {
+                    continue;
+                }
+
+                let world_pos = Vector2f::new(info.1 as f32 * tile_w, info.2 as f32 * tile_h);
+                tile.set_position(self.to_minimap(world_pos));
+                window.draw(&tile);
+            }
+        }
+    }
+
+    /// draw a small rotated triangle marker at `world_pos`, oriented by `angle` (radians)
+    fn draw_marker(&self, world_pos: Vector2f, angle: f32, color: Color, window: &mut RenderWindow) {
+        let mut marker = CircleShape::new(4.0, 3);
+        marker.set_fill_color(color);
+        marker.set_origin((4.0, 4.0));
+        marker.set_position(self.to_minimap(world_pos));
+        // the triangle's tip points up at rotation 0, while an angle of 0 faces +x
+        marker.set_rotation(r2d(angle) + 90.0);
+
+        window.draw(&marker);
+    }
+}
+
+// AI DRIVER ---
+
+/// a pure-pursuit controller that drives a `Car` by setting its existing input flags, so
+/// it can steer CPU opponents or run a demo/attract mode with no changes to the physics
+struct AiDriver {
+    waypoints: Vec<Vector2f>,
+    current: usize,
+    capture_radius: f32,
+}
+
+impl AiDriver {
+    fn new(waypoints: Vec<Vector2f>, capture_radius: f32) -> Self {
+        Self {
+            waypoints,
+            current: 0,
+            capture_radius,
+        }
+    }
+
+    /// steer `car` one step towards the current waypoint, advancing (and wrapping, for
+    /// lap behaviour) once it's within the capture radius
+    fn drive(&mut self, car: &mut Car) {
+        if self.waypoints.is_empty() {
+            return;
+        }
+
+        let target = self.waypoints[self.current];
+
+        if v2_length(target - car.get_position()) <= self.capture_radius {
+            self.current = (self.current + 1) % self.waypoints.len();
+        }
+
+        let target = self.waypoints[self.current];
+        let heading_to_target = v2_angle_to_point(target, car.get_position());
+        let heading_error = normalize_angle(heading_to_target - car.get_angle());
+
+        const STEER_DEADZONE: f32 = 0.05;
+        const SLOW_FOR_CORNER: f32 = PI / 2.0;
+
+        car.is_turning_left = heading_error < -STEER_DEADZONE;
+        car.is_turning_right = heading_error > STEER_DEADZONE;
+        car.is_reversing = false;
+        // ease off the throttle through sharp corners rather than ploughing straight on
+        car.is_moving = heading_error.abs() < SLOW_FOR_CORNER;
+    }
+}
 
 // CAR ---
 struct Car {
@@ -214,6 +860,9 @@ struct Car {
     car_angle: f32,
     steer_angle: f32,
 
+    wheel_radius: f32,
+    off_track: bool,
+
     is_moving: bool,
     is_reversing: bool,
     is_turning_left: bool,
@@ -239,6 +888,9 @@ impl Car {
             car_angle: 0.0,
             steer_angle: 75.0,
 
+            wheel_radius: 6.0,
+            off_track: false,
+
             is_moving: false,
             is_reversing: false,
             is_turning_left: false,
@@ -291,7 +943,13 @@ impl Car {
             self.velocity = Vector2f::new(0.0, 0.0);
         }
 
-        let mut f_force =  self.velocity * self.friction;
+        let friction = if self.off_track {
+            self.friction * 6.0
+        } else {
+            self.friction
+        };
+
+        let mut f_force =  self.velocity * friction;
         let d_force = self.velocity * speed * self.drag;
 
         if speed < 100.0 {
@@ -305,7 +963,7 @@ impl Car {
         self.acceleration += total;
     }
 
-    fn update(&mut self, dt: f32) {
+    fn update(&mut self, dt: f32, bounds: &TrackBounds) {
         let mut t = 0.0;
 
         if self.is_turning_left {
@@ -330,6 +988,18 @@ impl Car {
 
         self.steering(dt);
 
+        self.off_track = bounds.is_off_track(self.front_wheel) || bounds.is_off_track(self.back_wheel);
+
+        let pushed_front = bounds.push_back(self.front_wheel);
+        let pushed_back = bounds.push_back(self.back_wheel);
+
+        // carry the wheel correction over to the car's own position, otherwise it's
+        // discarded before next frame recomputes the wheels from the uncorrected position
+        let correction = ((pushed_front - self.front_wheel) + (pushed_back - self.back_wheel)) * 0.5;
+        self.position += correction;
+        self.front_wheel = pushed_front;
+        self.back_wheel = pushed_back;
+
         self.update_forces(dt);
 
         self.screen_wrap(1280.0, 896.0, 25.0);
@@ -353,7 +1023,29 @@ fn run(width: u32, height: u32) {
     tm.load("car", "assets/img/car.png");
     tm.load("sheet", "assets/img/spritesheet_tiles.png");
 
-    let mut car = Car::new(Vector2f::new(100.0, 100.0));
+    let mut sm = SoundManager::default();
+    sm.load("engine", "assets/audio/engine.wav");
+    sm.load("screech", "assets/audio/tire_screech.wav");
+
+    let mut engine_sound = Sound::with_buffer(sm.get("engine"));
+    engine_sound.set_looping(true);
+    engine_sound.play();
+
+    let mut screech_sound = Sound::with_buffer(sm.get("screech"));
+    let mut was_screeching = false;
+
+    let mut tl = TileLayer::new();
+    tl.set_up();
+
+    // spawn on the track surface itself, not wherever happens to be nearest the window
+    // origin - that's grass on the default track and would start the car off-track
+    let spawn = tl
+        .centerline(128.0, 128.0)
+        .first()
+        .copied()
+        .unwrap_or(Vector2f::new(100.0, 100.0));
+
+    let mut car = Car::new(spawn);
 
     let mut car_texture = Sprite::with_texture(tm.get("car"));
     let mut shadow = Sprite::with_texture(tm.get("car"));
@@ -366,8 +1058,26 @@ fn run(width: u32, height: u32) {
     shadow.set_origin((33.0 / 2.0, 18.0 / 2.0));
     shadow.set_position(car.get_position());
 
-    let mut tl = TileLayer::new();
-    tl.set_up();
+    let mut bounds = TrackBounds::new(&tl, 128.0, 128.0, car.wheel_radius);
+    let mut track_seed: u64 = 1;
+
+    let gauge = Gauge::new(Vector2f::new(100.0, height as f32 - 96.0), 60.0, 135.0, car.speed, 650.0);
+
+    let minimap = Minimap::new(
+        Vector2f::new(1280.0, 896.0),
+        FloatRect::new(width as f32 - 220.0, 20.0, 200.0, 140.0),
+    );
+
+    let speed_gradient = Gradient::new(vec![
+        (0.0, Color::rgb(60, 120, 220)),
+        (300.0, Color::rgb(230, 210, 60)),
+        (650.0, Color::rgb(220, 60, 60)),
+    ]);
+    let mut trail: Vec<(Vector2f, f32)> = Vec::new();
+    const TRAIL_LEN: usize = 40;
+
+    let mut ai = AiDriver::new(tl.centerline(128.0, 128.0), 64.0);
+    let mut ai_enabled = false;
 
     while window.is_open() {
         while let Some(event) = window.poll_event() {
@@ -376,6 +1086,31 @@ fn run(width: u32, height: u32) {
                 Event::KeyPressed { code, .. } => match code {
                     Key::Escape => window.close(),
                     Key::P => is_paused = !is_paused,
+                    Key::I => ai_enabled = !ai_enabled,
+                    // re-roll a fresh procedural track, reproducible per seed
+                    Key::G => {
+                        tl = TileLayer::generate(track_seed, tl.rows, tl.cols);
+                        tl.set_up();
+                        track_seed += 1;
+
+                        bounds = TrackBounds::new(&tl, 128.0, 128.0, car.wheel_radius);
+                        ai = AiDriver::new(tl.centerline(128.0, 128.0), 64.0);
+                    }
+                    // save the current track, or load back whatever was last saved
+                    Key::K => {
+                        if let Err(err) = tl.save("assets/tracks/current.trk") {
+                            eprintln!("failed to save track: {}", err);
+                        }
+                    }
+                    Key::L => match TileLayer::from_file("assets/tracks/current.trk") {
+                        Ok(mut loaded) => {
+                            loaded.set_up();
+                            bounds = TrackBounds::new(&loaded, 128.0, 128.0, car.wheel_radius);
+                            ai = AiDriver::new(loaded.centerline(128.0, 128.0), 64.0);
+                            tl = loaded;
+                        }
+                        Err(err) => eprintln!("failed to load track: {}", err),
+                    },
                     // car
                     Key::W => {
                         car.is_moving = true;
@@ -414,7 +1149,26 @@ fn run(width: u32, height: u32) {
         if !is_paused {
             let delta = clock.restart().as_seconds();
 
-            car.update(delta);
+            if ai_enabled {
+                ai.drive(&mut car);
+            }
+
+            car.update(delta, &bounds);
+
+            let speed = v2_length(car.velocity);
+            let speed_ratio = (speed / car.speed).clamp(0.0, 1.0);
+            engine_sound.set_pitch(0.8 + speed_ratio * 0.8);
+
+            trail.push((car.get_position(), speed));
+            if trail.len() > TRAIL_LEN {
+                trail.remove(0);
+            }
+
+            let is_screeching = (car.is_turning_left || car.is_turning_right) && speed > 300.0;
+            if is_screeching && !was_screeching {
+                screech_sound.play();
+            }
+            was_screeching = is_screeching;
 
             car_texture.set_position(car.get_position());
             car_texture.set_rotation(r2d(car.get_angle()));
@@ -447,9 +1201,16 @@ fn run(width: u32, height: u32) {
                 }
             }
 
+            draw_trail(&trail, &speed_gradient, &mut window);
+
             window.draw(&shadow);
             window.draw(&car_texture);
 
+            gauge.draw(v2_length(car.velocity), &mut window);
+
+            minimap.draw_track(&tl, 128.0, 128.0, &mut window);
+            minimap.draw_marker(car.get_position(), car.get_angle(), Color::rgb(230, 230, 230), &mut window);
+
             window.display();
         } else {
             clock.restart();